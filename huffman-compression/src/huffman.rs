@@ -15,11 +15,15 @@ const MAGIC_NUMBER: &[u8; 4] = b"HUFF";
 pub trait HuffBaseNode {
     fn is_leaf(&self) -> bool;
     fn weight(&self) -> u64;
+    /// Smallest symbol contained in this node's subtree, used only to break
+    /// weight ties in `build_tree` deterministically (see `min_symbol` on
+    /// `HuffInternalNode`).
+    fn min_symbol(&self) -> u8;
     fn as_any(&self) -> &dyn Any;
 }
 
 pub struct HuffLeafNode {
-    element: char,
+    element: u8,
     weight: u64,
 }
 
@@ -27,14 +31,15 @@ pub struct HuffInternalNode {
     left: HuffNode,
     right: HuffNode,
     weight: u64,
+    min_symbol: u8,
 }
 
 impl HuffLeafNode {
-    pub fn new(element: char, weight: u64) -> HuffLeafNode {
+    pub fn new(element: u8, weight: u64) -> HuffLeafNode {
         HuffLeafNode { element, weight }
     }
 
-    pub fn value(&self) -> char {
+    pub fn value(&self) -> u8 {
         self.element
     }
 }
@@ -48,6 +53,10 @@ impl HuffBaseNode for HuffLeafNode {
         self.weight
     }
 
+    fn min_symbol(&self) -> u8 {
+        self.element
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -55,10 +64,12 @@ impl HuffBaseNode for HuffLeafNode {
 
 impl HuffInternalNode {
     pub fn new(left: HuffNode, right: HuffNode, weight: u64) -> HuffInternalNode {
+        let min_symbol = left.min_symbol().min(right.min_symbol());
         HuffInternalNode {
             left,
             right,
             weight,
+            min_symbol,
         }
     }
 
@@ -80,6 +91,10 @@ impl HuffBaseNode for HuffInternalNode {
         self.weight
     }
 
+    fn min_symbol(&self) -> u8 {
+        self.min_symbol
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -87,12 +102,16 @@ impl HuffBaseNode for HuffInternalNode {
 
 impl PartialEq for dyn HuffBaseNode {
     fn eq(&self, other: &Self) -> bool {
-        self.weight() == other.weight()
+        self.weight() == other.weight() && self.min_symbol() == other.min_symbol()
     }
 }
 impl PartialOrd for dyn HuffBaseNode {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.weight().cmp(&other.weight()))
+        Some(
+            self.weight()
+                .cmp(&other.weight())
+                .then(self.min_symbol().cmp(&other.min_symbol())),
+        )
     }
 }
 impl Eq for dyn HuffBaseNode {}
@@ -142,22 +161,30 @@ pub struct HuffmanCompression {
     pub dst: String,
 }
 
+/// A Huffman code packed into its integer value and bit length, e.g.
+/// `val = 0b101, num_bits = 3` for the code `"101"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding {
+    pub val: u64,
+    pub num_bits: u8,
+}
+
 impl HuffmanCompression {
-    pub(crate) fn read(&self) -> Result<HashMap<char, u64>> {
+    pub(crate) fn read(&self) -> Result<HashMap<u8, u64>> {
         let mut file = File::open(&self.src)?;
-        let mut buf = String::new();
+        let mut buf = vec![];
         let mut table = HashMap::new();
-        file.read_to_string(&mut buf)?;
+        file.read_to_end(&mut buf)?;
 
-        for c in buf.chars() {
-            let frequency = table.get(&c).unwrap_or(&0);
-            table.insert(c, frequency + 1);
+        for b in buf {
+            let frequency = table.get(&b).unwrap_or(&0);
+            table.insert(b, frequency + 1);
         }
 
         Ok(table)
     }
 
-    pub(crate) fn build_tree(&self, table: HashMap<char, u64>) -> BoxedHuffNode {
+    pub(crate) fn build_tree(&self, table: HashMap<u8, u64>) -> BoxedHuffNode {
         let mut heap = BinaryHeap::new();
 
         for entry in table {
@@ -185,11 +212,10 @@ impl HuffmanCompression {
         heap.pop().unwrap()
     }
 
-    pub(crate) fn generate_huffman_code(&self, root: BoxedHuffNode) -> HashMap<char, String> {
-        let mut map: HashMap<char, String> = HashMap::new();
-        let mut bits = String::new();
-        dfs(root, &mut map, &mut bits);
-        map
+    pub(crate) fn generate_huffman_code(&self, root: BoxedHuffNode) -> HashMap<u8, Encoding> {
+        let mut lengths = HashMap::new();
+        dfs(root, &mut lengths, 0);
+        canonical_codes(lengths)
     }
 
     pub fn encode(&self) -> Result<()> {
@@ -197,10 +223,10 @@ impl HuffmanCompression {
         let root_node = self.build_tree(frequency_table);
         let lookup_table = self.generate_huffman_code(root_node);
 
-        let mut encoded_data = vec![];
-        self.write_header(&mut encoded_data, &lookup_table);
+        let (content, padding_bits) = self.encode_content(&lookup_table)?;
 
-        let content = self.encode_content(&lookup_table)?;
+        let mut encoded_data = vec![];
+        self.write_header(&mut encoded_data, &lookup_table, padding_bits);
         encoded_data.extend(content);
 
         let mut file = File::create(&self.dst)?;
@@ -222,60 +248,42 @@ impl HuffmanCompression {
 
         let mut buf = &buf[..];
         buf.get_u32();
+        let padding_bits = buf.get_u8();
         let table_len = buf.get_u32();
         let mut len = 0;
-        let mut lookup_table = HashMap::new();
 
+        let mut lengths = HashMap::new();
         while len < table_len {
-            let key_len = buf.get_u8();
-            let key = String::from_utf8(buf[..(key_len as usize)].to_vec())?;
-            buf = &buf[key_len as usize..];
-            let value_len = buf.get_u8();
-            let value = String::from_utf8(buf[..(value_len as usize)].to_vec())?;
-            buf = &buf[value_len as usize..];
-
-            let key = key.chars().nth(0).unwrap();
-            lookup_table.insert(value, key);
-            len += 1 + key_len as u32 + 1 + value_len as u32;
-        }
-
-        let content = buf[..].into_iter().map(|c| format!("{:08b}", c)).fold(
-            String::new(),
-            |mut content, c| {
-                content.push_str(&c);
-                content
-            },
-        );
+            let key = buf.get_u8();
+            let num_bits = buf.get_u8();
 
-        let mut matched_str = &content[..];
-        let mut decoded_data = String::new();
-
-        while matched_str.len() > 0 {
-            for (value, c) in &lookup_table {
-                if matched_str.starts_with(value) {
-                    decoded_data.push(*c);
-                    matched_str = &matched_str[value.len()..];
-                }
-            }
+            lengths.insert(key, num_bits);
+            len += 1 + 1;
         }
 
+        let tree = build_decode_tree(canonical_codes(lengths));
+        let decoded_data = decode_content(&tree, buf, padding_bits)?;
+
         let mut file = File::create(&self.dst)?;
-        file.write_all(decoded_data.as_bytes())?;
+        file.write_all(&decoded_data)?;
         file.flush()?;
 
         Ok(())
     }
 
-    fn write_header(&self, encoded_data: &mut Vec<u8>, table: &HashMap<char, String>) {
+    fn write_header(
+        &self,
+        encoded_data: &mut Vec<u8>,
+        table: &HashMap<u8, Encoding>,
+        padding_bits: u8,
+    ) {
         encoded_data.extend(MAGIC_NUMBER.iter().map(|c| c.clone()).collect::<Vec<_>>());
+        encoded_data.push(padding_bits);
 
         let mut buf = vec![];
-        for (key, value) in table.iter() {
-            let key = key.to_string();
-            buf.push(key.len() as u8);
-            buf.extend(key.as_bytes());
-            buf.push(value.len() as u8);
-            buf.extend(value.as_bytes());
+        for (key, code) in table.iter() {
+            buf.push(*key);
+            buf.push(code.num_bits);
         }
 
         let table_len = buf.len() as u32;
@@ -283,36 +291,44 @@ impl HuffmanCompression {
         encoded_data.extend(buf);
     }
 
-    fn encode_content(&self, table: &HashMap<char, String>) -> Result<Vec<u8>> {
+    fn encode_content(&self, table: &HashMap<u8, Encoding>) -> Result<(Vec<u8>, u8)> {
         let mut file = File::open(&self.src)?;
-        let mut buf = String::new();
-        file.read_to_string(&mut buf)?;
+        let mut buf = vec![];
+        file.read_to_end(&mut buf)?;
 
-        let mut encoded_data = String::new();
-        for c in buf.chars() {
-            encoded_data.push_str(&table[&c]);
+        let mut encoded_data = vec![];
+        let mut current_byte = 0u8;
+        let mut bit_count = 0u8;
+
+        for b in buf {
+            let code = &table[&b];
+            for i in (0..code.num_bits).rev() {
+                let bit = (code.val >> i) & 1;
+                current_byte = (current_byte << 1) | bit as u8;
+                bit_count += 1;
+
+                if bit_count == 8 {
+                    encoded_data.push(current_byte);
+                    current_byte = 0;
+                    bit_count = 0;
+                }
+            }
         }
 
-        while encoded_data.len() % 8 != 0 {
-            encoded_data.push('0');
+        let padding_bits = if bit_count > 0 { 8 - bit_count } else { 0 };
+        if bit_count > 0 {
+            current_byte <<= padding_bits;
+            encoded_data.push(current_byte);
         }
 
-        Ok(encoded_data
-            .chars()
-            .collect::<Vec<_>>()
-            .chunks(8)
-            .map(|c| {
-                let binary_str = c.iter().collect::<String>();
-                u8::from_str_radix(&binary_str, 2).unwrap()
-            })
-            .collect::<Vec<u8>>())
+        Ok((encoded_data, padding_bits))
     }
 }
 
-fn dfs(root: BoxedHuffNode, map: &mut HashMap<char, String>, bits: &mut String) {
+fn dfs(root: BoxedHuffNode, lengths: &mut HashMap<u8, u8>, depth: u8) {
     if root.is_leaf() {
         let node = root.inner.as_any().downcast_ref::<HuffLeafNode>().unwrap();
-        map.insert(node.value(), bits.to_string());
+        lengths.insert(node.value(), depth);
         return;
     }
 
@@ -322,11 +338,116 @@ fn dfs(root: BoxedHuffNode, map: &mut HashMap<char, String>, bits: &mut String)
         .downcast_ref::<HuffInternalNode>()
         .unwrap();
 
-    bits.push('0');
-    dfs(BoxedHuffNode::new(node.left().clone()), map, bits);
-    bits.remove(bits.len() - 1);
+    dfs(BoxedHuffNode::new(node.left().clone()), lengths, depth + 1);
+    dfs(
+        BoxedHuffNode::new(node.right().clone()),
+        lengths,
+        depth + 1,
+    );
+}
+
+/// A binary trie used to decode packed Huffman bits one bit at a time,
+/// rather than scanning the whole `lookup_table` for every position.
+#[derive(Default)]
+struct DecodeNode {
+    leaf: Option<u8>,
+    left: Option<Box<DecodeNode>>,
+    right: Option<Box<DecodeNode>>,
+}
+
+impl DecodeNode {
+    fn insert(&mut self, code: Encoding, symbol: u8) {
+        let mut node = self;
+        for i in (0..code.num_bits).rev() {
+            let bit = (code.val >> i) & 1;
+            node = if bit == 0 {
+                node.left.get_or_insert_with(Default::default)
+            } else {
+                node.right.get_or_insert_with(Default::default)
+            };
+        }
+        node.leaf = Some(symbol);
+    }
+}
+
+fn build_decode_tree(codes: HashMap<u8, Encoding>) -> DecodeNode {
+    let mut root = DecodeNode::default();
+    for (symbol, code) in codes {
+        root.insert(code, symbol);
+    }
+    root
+}
+
+/// Walks `tree` one bit at a time over the packed `content` buffer, emitting
+/// a symbol each time a leaf is reached and resetting to the root, so no
+/// per-bit `String` is materialized; apart from the input slice and the
+/// returned output, memory is O(1) in the content size. `padding_bits`
+/// trailing bits are dropped from the last byte.
+fn decode_content(tree: &DecodeNode, content: &[u8], padding_bits: u8) -> Result<Vec<u8>> {
+    let total_bits = content.len() * 8 - padding_bits as usize;
+    let mut decoded = vec![];
+    let mut node = tree;
+
+    for i in 0..total_bits {
+        let bit = (content[i / 8] >> (7 - i % 8)) & 1;
+        node = if bit == 0 {
+            node.left.as_deref()
+        } else {
+            node.right.as_deref()
+        }
+        .ok_or_else(|| anyhow::anyhow!("no Huffman code matches the remaining bits"))?;
+
+        if let Some(symbol) = node.leaf {
+            decoded.push(symbol);
+            node = tree;
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// Assigns canonical Huffman codes from a symbol -> code-length map: symbols
+/// are ordered by `(length, symbol)`, the first gets code `0`, and each
+/// subsequent code is `(prev_code + 1) << (len - prev_len)`. Running this on
+/// the same lengths always produces the same codes, so only the lengths
+/// need to travel in the header.
+fn canonical_codes(lengths: HashMap<u8, u8>) -> HashMap<u8, Encoding> {
+    let mut symbols: Vec<(u8, u8)> = lengths.into_iter().collect();
+    symbols.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+
+    // A single-symbol alphabet builds a tree that is just a bare leaf, so
+    // `dfs` reports a length of 0 for it. Force a 1-bit code instead, or
+    // `encode_content` would write zero bits per occurrence and the decoder
+    // would have nothing to walk, losing the content entirely.
+    if let [(symbol, _)] = symbols[..] {
+        let mut map = HashMap::new();
+        map.insert(
+            symbol,
+            Encoding {
+                val: 0,
+                num_bits: 1,
+            },
+        );
+        return map;
+    }
+
+    let mut map = HashMap::new();
+    let mut code = 0u64;
+    let mut prev_len = 0u8;
+
+    for (i, (symbol, len)) in symbols.into_iter().enumerate() {
+        if i > 0 {
+            code = (code + 1) << (len - prev_len);
+        }
+        map.insert(
+            symbol,
+            Encoding {
+                val: code,
+                num_bits: len,
+            },
+        );
+        prev_len = len;
+    }
 
-    bits.push('1');
-    dfs(BoxedHuffNode::new(node.right().clone()), map, bits);
-    bits.remove(bits.len() - 1);
+    map
 }
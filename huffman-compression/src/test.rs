@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use huffman::{HuffInternalNode, HuffLeafNode};
+use huffman::{Encoding, HuffInternalNode, HuffLeafNode};
 
 use super::*;
 
@@ -16,8 +16,8 @@ fn init_huffman_compression() -> HuffmanCompression {
 fn test_table_character_count() {
     let huffman = init_huffman_compression();
     let table = huffman.read().unwrap();
-    assert_eq!(table.get(&'X').unwrap(), &333);
-    assert_eq!(table.get(&'t').unwrap(), &223000);
+    assert_eq!(table.get(&b'X').unwrap(), &333);
+    assert_eq!(table.get(&b't').unwrap(), &223000);
 }
 
 #[test]
@@ -25,14 +25,14 @@ fn test_build_huffman_tree() {
     let huffman = init_huffman_compression();
 
     let mut table = HashMap::new();
-    table.insert('C', 32);
-    table.insert('D', 42);
-    table.insert('E', 120);
-    table.insert('K', 7);
-    table.insert('L', 42);
-    table.insert('M', 24);
-    table.insert('U', 37);
-    table.insert('Z', 2);
+    table.insert(b'C', 32);
+    table.insert(b'D', 42);
+    table.insert(b'E', 120);
+    table.insert(b'K', 7);
+    table.insert(b'L', 42);
+    table.insert(b'M', 24);
+    table.insert(b'U', 37);
+    table.insert(b'Z', 2);
 
     let root = huffman.build_tree(table);
     assert!(!root.is_leaf());
@@ -47,7 +47,7 @@ fn test_build_huffman_tree() {
     let left_leaf = left_leaf.as_any().downcast_ref::<HuffLeafNode>();
     assert!(left_leaf.is_some());
     let left_leaf = left_leaf.unwrap();
-    assert_eq!(left_leaf.value(), 'E');
+    assert_eq!(left_leaf.value(), b'E');
 
     let right_node = root.right();
     assert!(!right_node.is_leaf());
@@ -62,24 +62,75 @@ fn test_huffman_lookup_table() {
     let huffman = init_huffman_compression();
 
     let mut table = HashMap::new();
-    table.insert('C', 32);
-    table.insert('D', 42);
-    table.insert('E', 120);
-    table.insert('K', 7);
-    table.insert('L', 42);
-    table.insert('M', 24);
-    table.insert('U', 37);
-    table.insert('Z', 2);
+    table.insert(b'C', 32);
+    table.insert(b'D', 42);
+    table.insert(b'E', 120);
+    table.insert(b'K', 7);
+    table.insert(b'L', 42);
+    table.insert(b'M', 24);
+    table.insert(b'U', 37);
+    table.insert(b'Z', 2);
 
+    // Canonical codes are assigned by (length, symbol) order rather than
+    // tree shape, so these reflect the canonical assignment, not a raw
+    // tree walk: E(1) < D,L,U(3) < C(4) < M(5) < K,Z(6).
     let mut lookup_table = HashMap::new();
-    lookup_table.insert('C', "1110".to_owned());
-    lookup_table.insert('D', "101".to_owned());
-    lookup_table.insert('E', "0".to_owned());
-    lookup_table.insert('K', "111101".to_owned());
-    lookup_table.insert('L', "110".to_owned());
-    lookup_table.insert('M', "11111".to_owned());
-    lookup_table.insert('U', "100".to_owned());
-    lookup_table.insert('Z', "111100".to_owned());
+    lookup_table.insert(
+        b'E',
+        Encoding {
+            val: 0b0,
+            num_bits: 1,
+        },
+    );
+    lookup_table.insert(
+        b'D',
+        Encoding {
+            val: 0b100,
+            num_bits: 3,
+        },
+    );
+    lookup_table.insert(
+        b'L',
+        Encoding {
+            val: 0b101,
+            num_bits: 3,
+        },
+    );
+    lookup_table.insert(
+        b'U',
+        Encoding {
+            val: 0b110,
+            num_bits: 3,
+        },
+    );
+    lookup_table.insert(
+        b'C',
+        Encoding {
+            val: 0b1110,
+            num_bits: 4,
+        },
+    );
+    lookup_table.insert(
+        b'M',
+        Encoding {
+            val: 0b11110,
+            num_bits: 5,
+        },
+    );
+    lookup_table.insert(
+        b'K',
+        Encoding {
+            val: 0b111110,
+            num_bits: 6,
+        },
+    );
+    lookup_table.insert(
+        b'Z',
+        Encoding {
+            val: 0b111111,
+            num_bits: 6,
+        },
+    );
 
     let root = huffman.build_tree(table);
     let res = huffman.generate_huffman_code(root);
@@ -92,3 +143,50 @@ fn test_encode_file() {
     let huffman = init_huffman_compression();
     huffman.encode().unwrap();
 }
+
+#[test]
+fn test_encode_decode_roundtrip() {
+    let data = vec![b'x'; 50];
+    std::fs::write("./roundtrip_src.txt", &data).unwrap();
+
+    let huffman = HuffmanCompression {
+        src: "./roundtrip_src.txt".to_string(),
+        dst: "./roundtrip.bin".to_string(),
+    };
+    huffman.encode().unwrap();
+
+    let huffman = HuffmanCompression {
+        src: "./roundtrip.bin".to_string(),
+        dst: "./roundtrip_dst.txt".to_string(),
+    };
+    huffman.decode().unwrap();
+
+    let decoded = std::fs::read("./roundtrip_dst.txt").unwrap();
+    assert_eq!(decoded, data);
+}
+
+#[test]
+fn test_encode_decode_roundtrip_multi_symbol() {
+    // Many distinct (and non-ASCII) byte values with skewed frequencies,
+    // at a length that isn't a multiple of 8 bits once encoded, so this
+    // exercises canonical code assignment, bit packing, and padding
+    // together rather than just the single-symbol edge case.
+    let mut data: Vec<u8> = (0..=255u8).collect();
+    data.extend_from_slice(b"huffman compression rocks");
+    std::fs::write("./roundtrip_multi_src.txt", &data).unwrap();
+
+    let huffman = HuffmanCompression {
+        src: "./roundtrip_multi_src.txt".to_string(),
+        dst: "./roundtrip_multi.bin".to_string(),
+    };
+    huffman.encode().unwrap();
+
+    let huffman = HuffmanCompression {
+        src: "./roundtrip_multi.bin".to_string(),
+        dst: "./roundtrip_multi_dst.txt".to_string(),
+    };
+    huffman.decode().unwrap();
+
+    let decoded = std::fs::read("./roundtrip_multi_dst.txt").unwrap();
+    assert_eq!(decoded, data);
+}